@@ -26,18 +26,318 @@ use comfy_table::Cell;
 use comfy_table::Color;
 use comfy_table::Table;
 use common_base::ProgressValues;
+use futures::StreamExt;
 use lexical_util::num::AsPrimitive;
 use num_format::Locale;
 use num_format::ToFormattedString;
 
 use crate::cmds::clusters::cluster::ClusterProfile;
 use crate::cmds::command::Command;
+use crate::cmds::status::LocalQueryConfig;
 use crate::cmds::Config;
 use crate::cmds::Status;
 use crate::cmds::Writer;
 use crate::error::CliError;
 use crate::error::Result;
 
+/// TLS material overrides taken from the CLI, falling back to the paths
+/// discovered from local configs when left unset.
+#[derive(Clone, Default)]
+pub struct TlsOptions {
+    pub ca: Option<String>,
+    pub cert: Option<String>,
+    pub key: Option<String>,
+}
+
+impl TlsOptions {
+    fn from_args(args: &ArgMatches) -> Self {
+        TlsOptions {
+            ca: args.value_of("tls-ca").map(|s| s.to_string()),
+            cert: args.value_of("tls-cert").map(|s| s.to_string()),
+            key: args.value_of("tls-key").map(|s| s.to_string()),
+        }
+    }
+}
+
+/// How a query's result should be rendered: the interactive `comfy_table`
+/// (default) or a scripting-friendly structured format.
+#[derive(Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Ndjson,
+    Csv,
+    Tsv,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = CliError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            "csv" => Ok(OutputFormat::Csv),
+            "tsv" => Ok(OutputFormat::Tsv),
+            _ => Err(CliError::Unknown(format!(
+                "unsupported output format: {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Per-statement execution knobs threaded through the session/router/writer
+/// call chain, parsed once from the CLI args.
+#[derive(Clone)]
+pub struct QueryOptions {
+    pub stream: bool,
+    pub output: OutputFormat,
+    pub retries: u32,
+    pub retry_backoff_ms: u64,
+}
+
+impl QueryOptions {
+    fn from_args(args: &ArgMatches) -> Result<Self> {
+        let output = args
+            .value_of("output")
+            .unwrap_or("table")
+            .parse::<OutputFormat>()?;
+        let retries = args
+            .value_of_t("retries")
+            .map_err(|e| CliError::Unknown(format!("invalid --retries value: {:?}", e)))?;
+        let retry_backoff_ms = args
+            .value_of_t("retry-backoff-ms")
+            .map_err(|e| CliError::Unknown(format!("invalid --retry-backoff-ms value: {:?}", e)))?;
+        Ok(QueryOptions {
+            stream: args.is_present("stream"),
+            output,
+            retries,
+            retry_backoff_ms,
+        })
+    }
+}
+
+/// A failed query attempt, distinguishing errors worth retrying (connection
+/// failures, timeouts, 5xx responses) from fatal ones (a successfully
+/// delivered 4xx query error, a malformed result body) that a retry can't fix.
+enum QueryAttemptError {
+    Retryable(CliError),
+    Fatal(CliError),
+}
+
+/// Dispatches a statement to either the single local query node or a cluster
+/// of nodes, hiding the round-robin/failover bookkeeping from the session
+/// driver so both profiles share one execution path.
+pub enum QueryRouter {
+    Local {
+        cli: reqwest::Client,
+        url: String,
+    },
+    Cluster {
+        endpoints: Vec<(reqwest::Client, String)>,
+    },
+}
+
+impl QueryRouter {
+    async fn execute(
+        &self,
+        status: &mut Status,
+        query: String,
+        database: Option<&str>,
+        opts: &QueryOptions,
+        writer: &mut Writer,
+    ) -> Result<()> {
+        match self {
+            QueryRouter::Local { cli, url } => {
+                writer.write_ok(format!("Execute query {} on {}", query, url).as_str());
+                query_writer(cli, url.as_str(), query, database, opts, writer).await
+            }
+            QueryRouter::Cluster { endpoints } => {
+                let mut last_err = None;
+                for _ in 0..endpoints.len() {
+                    let (cli, url) = match pick_cluster_endpoint(endpoints, status).await {
+                        Some(endpoint) => endpoint,
+                        None => break,
+                    };
+                    writer.write_ok(format!("Execute query {} on {}", query, url).as_str());
+                    match query_writer(&cli, url.as_str(), query.clone(), database, opts, writer)
+                        .await
+                    {
+                        Ok(_) => return Ok(()),
+                        Err(e) => {
+                            writer.write_err(
+                                format!(
+                                    "query {} failed on {}, failing over to next node: {:?}",
+                                    query, url, e
+                                )
+                                .as_str(),
+                            );
+                            last_err = Some(e);
+                        }
+                    }
+                }
+                Err(last_err.unwrap_or_else(|| {
+                    CliError::Unknown("no live query node found in cluster".to_string())
+                }))
+            }
+        }
+    }
+}
+
+/// Extract the database name from a `USE <database>;` statement, stripping
+/// surrounding quoting and the trailing `;`. Returns `None` for any other
+/// statement.
+fn parse_use_database(statement: &str) -> Option<String> {
+    let trimmed = statement.trim().trim_end_matches(';').trim();
+    let mut parts = trimmed.splitn(2, char::is_whitespace);
+    let keyword = parts.next()?;
+    if !keyword.eq_ignore_ascii_case("use") {
+        return None;
+    }
+    let db = parts.next()?.trim().trim_matches('`').trim_matches('"');
+    if db.is_empty() {
+        None
+    } else {
+        Some(db.to_string())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SplitterState {
+    Normal,
+    SingleQuoted,
+    DoubleQuoted,
+    Backtick,
+    DollarQuoted,
+    LineComment,
+    BlockComment,
+}
+
+/// Split a blob of SQL text into individual statements on top-level `;`
+/// separators, tracking single/double/backtick/dollar-quoted strings and
+/// `--`/`/* */` comments so a `;` inside any of them isn't mistaken for a
+/// statement boundary. Each returned statement keeps its original text
+/// (embedded `;` included) with only leading/trailing whitespace trimmed;
+/// empty statements are dropped.
+fn split_statements(input: &str) -> Vec<String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut state = SplitterState::Normal;
+    let mut start = 0usize;
+    let mut statements = Vec::new();
+    let mut dollar_tag = String::new();
+    let mut i = 0usize;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match state {
+            SplitterState::Normal => match c {
+                '\'' => state = SplitterState::SingleQuoted,
+                '"' => state = SplitterState::DoubleQuoted,
+                '`' => state = SplitterState::Backtick,
+                '$' => {
+                    if let Some((tag, end)) = match_dollar_tag(&chars, i) {
+                        dollar_tag = tag;
+                        state = SplitterState::DollarQuoted;
+                        i = end;
+                    }
+                }
+                '-' if chars.get(i + 1) == Some(&'-') => {
+                    state = SplitterState::LineComment;
+                    i += 1;
+                }
+                '/' if chars.get(i + 1) == Some(&'*') => {
+                    state = SplitterState::BlockComment;
+                    i += 1;
+                }
+                ';' => {
+                    push_statement(&mut statements, &chars[start..i]);
+                    start = i + 1;
+                }
+                _ => {}
+            },
+            SplitterState::SingleQuoted => {
+                if c == '\'' {
+                    if chars.get(i + 1) == Some(&'\'') {
+                        i += 1;
+                    } else {
+                        state = SplitterState::Normal;
+                    }
+                }
+            }
+            SplitterState::DoubleQuoted => {
+                if c == '"' {
+                    if chars.get(i + 1) == Some(&'"') {
+                        i += 1;
+                    } else {
+                        state = SplitterState::Normal;
+                    }
+                }
+            }
+            SplitterState::Backtick => {
+                if c == '`' {
+                    state = SplitterState::Normal;
+                }
+            }
+            SplitterState::DollarQuoted => {
+                if c == '$' {
+                    if let Some((tag, end)) = match_dollar_tag(&chars, i) {
+                        if tag == dollar_tag {
+                            state = SplitterState::Normal;
+                            i = end;
+                        }
+                    }
+                }
+            }
+            SplitterState::LineComment => {
+                if c == '\n' {
+                    state = SplitterState::Normal;
+                }
+            }
+            SplitterState::BlockComment => {
+                if c == '*' && chars.get(i + 1) == Some(&'/') {
+                    i += 1;
+                    state = SplitterState::Normal;
+                }
+            }
+        }
+        i += 1;
+    }
+
+    push_statement(&mut statements, &chars[start..]);
+    statements
+}
+
+fn push_statement(statements: &mut Vec<String>, chars: &[char]) {
+    let trimmed = chars.iter().collect::<String>().trim().to_string();
+    if !trimmed.is_empty() {
+        statements.push(trimmed);
+    }
+}
+
+/// Match a dollar-quote tag (`$$` or `$tag$`) starting at `chars[i]`,
+/// returning the tag name and the index of its closing `$`.
+fn match_dollar_tag(chars: &[char], i: usize) -> Option<(String, usize)> {
+    if chars.get(i) != Some(&'$') {
+        return None;
+    }
+    let mut tag = String::new();
+    let mut j = i + 1;
+    while let Some(&c) = chars.get(j) {
+        if c == '$' {
+            return Some((tag, j));
+        }
+        if c.is_alphanumeric() || c == '_' {
+            tag.push(c);
+            j += 1;
+        } else {
+            return None;
+        }
+    }
+    None
+}
+
 #[derive(Clone)]
 pub struct QueryCommand {
     #[allow(dead_code)]
@@ -59,7 +359,7 @@ impl QueryCommand {
                     .long("profile")
                     .about("Profile to run queries")
                     .required(false)
-                    .possible_values(&["local"])
+                    .possible_values(&["local", "cluster"])
                     .default_value("local"),
             )
             .arg(
@@ -67,6 +367,60 @@ impl QueryCommand {
                     .about("Query statements to run")
                     .takes_value(true)
                     .required(true),
+            )
+            .arg(
+                Arg::new("tls-ca")
+                    .long("tls-ca")
+                    .about("Path to the TLS CA certificate used to verify the query node")
+                    .takes_value(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new("tls-cert")
+                    .long("tls-cert")
+                    .about("Path to the client TLS certificate used for mTLS")
+                    .takes_value(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new("tls-key")
+                    .long("tls-key")
+                    .about("Path to the client TLS private key used for mTLS")
+                    .takes_value(true)
+                    .required(false),
+            )
+            .arg(
+                Arg::new("stream")
+                    .long("stream")
+                    .about("Render a live progress bar while the query is still running")
+                    .takes_value(false)
+                    .required(false),
+            )
+            .arg(
+                Arg::new("output")
+                    .long("output")
+                    .short('o')
+                    .about("Output format for query results")
+                    .takes_value(true)
+                    .possible_values(&["table", "json", "ndjson", "csv", "tsv"])
+                    .default_value("table")
+                    .required(false),
+            )
+            .arg(
+                Arg::new("retries")
+                    .long("retries")
+                    .about("Number of times to retry a statement on connection errors or 5xx responses")
+                    .takes_value(true)
+                    .default_value("3")
+                    .required(false),
+            )
+            .arg(
+                Arg::new("retry-backoff-ms")
+                    .long("retry-backoff-ms")
+                    .about("Base delay in milliseconds between retries, doubled on each attempt")
+                    .takes_value(true)
+                    .default_value("200")
+                    .required(false),
             );
         app
     }
@@ -84,7 +438,7 @@ impl QueryCommand {
                         return self.local_exec_match(writer, matches).await;
                     }
                     Ok(ClusterProfile::Cluster) => {
-                        todo!()
+                        return self.cluster_exec_match(writer, matches).await;
                     }
                     Err(_) => writer.write_err("currently profile only support cluster or local"),
                 }
@@ -96,75 +450,192 @@ impl QueryCommand {
         Ok(())
     }
 
+    async fn cluster_exec_match(&self, writer: &mut Writer, args: &ArgMatches) -> Result<()> {
+        match self.local_exec_precheck(args) {
+            Ok(_) => {
+                writer.write_ok("Query precheck passed!");
+                let mut status = Status::read(self.conf.clone())?;
+                let tls = TlsOptions::from_args(args);
+                let endpoints = build_cluster_query_endpoints(&status, &tls)?;
+                let router = QueryRouter::Cluster { endpoints };
+                self.run_session(writer, args, &mut status, &router).await
+            }
+            Err(e) => {
+                writer.write_err(&*format!("Query command precheck failed, error {:?}", e));
+                Ok(())
+            }
+        }
+    }
+
     async fn local_exec_match(&self, writer: &mut Writer, args: &ArgMatches) -> Result<()> {
         match self.local_exec_precheck(args) {
             Ok(_) => {
                 writer.write_ok("Query precheck passed!");
-                let status = Status::read(self.conf.clone())?;
-                let queries = match args.value_of("query") {
-                    Some(val) => {
-                        if Path::new(val).exists() {
-                            let buffer =
-                                std::fs::read(Path::new(val)).expect("cannot read query from file");
-                            String::from_utf8_lossy(&*buffer).to_string()
-                        } else if val.starts_with("http://") || val.starts_with("https://") {
-                            let res = reqwest::get(val)
-                                .await
-                                .expect("cannot fetch query from url")
-                                .text()
-                                .await
-                                .expect("cannot fetch response body");
-                            res
-                        } else {
-                            val.to_string()
-                        }
-                    }
-                    None => {
-                        let mut buffer = String::new();
-                        std::io::stdin()
-                            .read_to_string(&mut buffer)
-                            .expect("cannot read from stdin");
-                        buffer
-                    }
+                let mut status = Status::read(self.conf.clone())?;
+                let tls = TlsOptions::from_args(args);
+                let (cli, url) = build_query_endpoint(&status, &tls)?;
+                let router = QueryRouter::Local { cli, url };
+                self.run_session(writer, args, &mut status, &router).await
+            }
+            Err(e) => {
+                writer.write_err(&*format!("Query command precheck failed, error {:?}", e));
+                Ok(())
+            }
+        }
+    }
+
+    /// Drive either a one-shot batch of statements (from the `query` argument
+    /// as SQL text, a file, a
+    /// URL, or piped stdin) or, when no query was given and stdin is a
+    /// terminal, an interactive `USE <database>`-aware REPL.
+    async fn run_session(
+        &self,
+        writer: &mut Writer,
+        args: &ArgMatches,
+        status: &mut Status,
+        router: &QueryRouter,
+    ) -> Result<()> {
+        let opts = QueryOptions::from_args(args)?;
+
+        match args.value_of("query") {
+            Some(val) => {
+                let queries = if Path::new(val).exists() {
+                    let buffer = std::fs::read(Path::new(val)).map_err(|e| {
+                        CliError::Unknown(format!("cannot read query from file {}: {:?}", val, e))
+                    })?;
+                    String::from_utf8_lossy(&*buffer).to_string()
+                } else if val.starts_with("http://") || val.starts_with("https://") {
+                    reqwest::get(val)
+                        .await
+                        .map_err(|e| {
+                            CliError::Unknown(format!("cannot fetch query from url: {:?}", e))
+                        })?
+                        .text()
+                        .await
+                        .map_err(|e| {
+                            CliError::Unknown(format!("cannot fetch response body: {:?}", e))
+                        })?
+                } else {
+                    val.to_string()
                 };
+                self.run_batch(writer, status, router, &queries, &opts)
+                    .await
+            }
+            None if atty::is(atty::Stream::Stdin) => {
+                self.run_repl(writer, status, router, &opts).await
+            }
+            None => {
+                let mut buffer = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut buffer)
+                    .map_err(|e| CliError::Unknown(format!("cannot read from stdin: {:?}", e)))?;
+                self.run_batch(writer, status, router, &buffer, &opts)
+                    .await
+            }
+        }
+    }
 
-                let res = build_query_endpoint(&status);
+    async fn run_batch(
+        &self,
+        writer: &mut Writer,
+        status: &mut Status,
+        router: &QueryRouter,
+        queries: &str,
+        opts: &QueryOptions,
+    ) -> Result<()> {
+        for query in split_statements(queries) {
+            if let Err(e) = self.run_statement(writer, status, router, query.clone(), opts).await {
+                writer.write_err(format!("query {} execution error: {:?}", query, e).as_str());
+            }
+        }
+        status.write()?;
+        Ok(())
+    }
 
-                if let Ok((cli, url)) = res {
-                    for query in queries
-                        .split(';')
-                        .filter(|elem| !elem.trim().is_empty())
-                        .map(|elem| format!("{};", elem))
-                        .collect::<Vec<String>>()
-                    {
-                        writer.write_ok(
-                            format!("Execute query {} on {}", query.clone(), url).as_str(),
-                        );
-                        if let Err(e) =
-                            query_writer(&cli, url.as_str(), query.clone(), writer).await
-                        {
-                            writer.write_err(
-                                format!("query {} execution error: {:?}", query, e).as_str(),
-                            );
+    /// A psql-like interactive shell: accumulates input until a terminating
+    /// `;`, keeps command history, and shows the current database (set via
+    /// `USE <database>;`) in the prompt.
+    async fn run_repl(
+        &self,
+        writer: &mut Writer,
+        status: &mut Status,
+        router: &QueryRouter,
+        opts: &QueryOptions,
+    ) -> Result<()> {
+        let history_path = Path::new(&status.local_config_dir).join(".bendctl_history");
+        let mut editor = rustyline::Editor::<()>::new();
+        let _ = editor.load_history(&history_path);
+
+        let mut pending = String::new();
+        loop {
+            let db = status.current_database.clone().unwrap_or_else(|| "default".to_string());
+            let prompt = if pending.is_empty() {
+                format!("{}> ", db)
+            } else {
+                "... ".to_string()
+            };
+            match editor.readline(&prompt) {
+                Ok(line) => {
+                    if pending.is_empty() && matches!(line.trim(), "exit" | "quit" | "\\q") {
+                        break;
+                    }
+                    if !pending.is_empty() {
+                        pending.push('\n');
+                    }
+                    pending.push_str(&line);
+                    if line.trim_end().ends_with(';') {
+                        let block = std::mem::take(&mut pending);
+                        editor.add_history_entry(block.as_str());
+                        for stmt in split_statements(&block) {
+                            if let Err(e) = self
+                                .run_statement(writer, status, router, stmt.clone(), opts)
+                                .await
+                            {
+                                writer.write_err(
+                                    format!("query {} execution error: {:?}", stmt, e).as_str(),
+                                );
+                            }
                         }
                     }
-                } else {
-                    writer.write_err(
-                        format!(
-                            "Query command error: cannot parse query url with error: {:?}",
-                            res.unwrap_err()
-                        )
-                        .as_str(),
-                    );
                 }
-
-                Ok(())
-            }
-            Err(e) => {
-                writer.write_err(&*format!("Query command precheck failed, error {:?}", e));
-                Ok(())
+                Err(rustyline::error::ReadlineError::Interrupted)
+                | Err(rustyline::error::ReadlineError::Eof) => break,
+                Err(e) => {
+                    writer.write_err(format!("readline error: {:?}", e).as_str());
+                    break;
+                }
             }
         }
+        let _ = editor.save_history(&history_path);
+        status.write()?;
+        Ok(())
+    }
+
+    /// Execute one statement, threading the session's current database
+    /// through: a `USE <database>;` statement updates and persists the
+    /// session context instead of being sent to the server, and every other
+    /// statement is sent as-is with the current database attached
+    /// out-of-band (an `x-databend-database` request header) so the SQL text
+    /// and the `Execute query ...` log line always reflect what the user
+    /// actually typed.
+    async fn run_statement(
+        &self,
+        writer: &mut Writer,
+        status: &mut Status,
+        router: &QueryRouter,
+        statement: String,
+        opts: &QueryOptions,
+    ) -> Result<()> {
+        if let Some(db) = parse_use_database(&statement) {
+            status.current_database = Some(db);
+            status.write()?;
+            return Ok(());
+        }
+
+        let database = status.current_database.clone();
+        router
+            .execute(status, statement, database.as_deref(), opts, writer)
+            .await
     }
 
     /// precheck whether current local profile applicable for local host machine
@@ -181,24 +652,63 @@ impl QueryCommand {
     }
 }
 
+/// Exponential backoff delay for retry attempt `attempt` (0-indexed),
+/// doubling `base_ms` each attempt. Uses `checked_shl` so an unclamped
+/// `--retries` value (e.g. 64+) saturates to `u64::MAX` instead of
+/// panicking on overflow.
+fn compute_backoff_ms(base_ms: u64, attempt: u32) -> u64 {
+    base_ms.saturating_mul(1u64.checked_shl(attempt).unwrap_or(u64::MAX))
+}
+
 async fn query_writer(
     cli: &reqwest::Client,
     url: &str,
     query: String,
+    database: Option<&str>,
+    opts: &QueryOptions,
     writer: &mut Writer,
 ) -> Result<()> {
     let start = std::time::Instant::now();
-    match execute_query(cli, url, query).await {
-        Ok((res, stats)) => {
+    let mut attempt = 0u32;
+    let result = loop {
+        let attempt_result = if opts.stream {
+            execute_query_streaming(cli, url, query.clone(), database, writer).await
+        } else {
+            execute_query(cli, url, query.clone(), database).await
+        };
+        match attempt_result {
+            Ok(ok) => break Ok(ok),
+            Err(QueryAttemptError::Fatal(e)) => break Err(e),
+            Err(QueryAttemptError::Retryable(e)) => {
+                if attempt >= opts.retries {
+                    break Err(e);
+                }
+                let backoff_ms = compute_backoff_ms(opts.retry_backoff_ms, attempt);
+                writer.write_err(
+                    format!(
+                        "query attempt {} failed, retrying in {}ms: {:?}",
+                        attempt + 1,
+                        backoff_ms,
+                        e
+                    )
+                    .as_str(),
+                );
+                tokio::time::sleep(std::time::Duration::from_millis(backoff_ms)).await;
+                attempt += 1;
+            }
+        }
+    };
+    match result {
+        Ok((data, stats)) => {
             let elapsed = start.elapsed();
-            writer.writeln(res.trim_fmt().as_str());
+            write_result(writer, &data, opts.output);
             if let Some(stat) = stats {
                 let time = elapsed.as_millis() as f64 / 1000f64;
                 let byte_per_sec = byte_unit::Byte::from_unit(
                     stat.read_bytes as f64 / time,
                     byte_unit::ByteUnit::B,
                 )
-                .expect("cannot parse byte")
+                .unwrap_or_else(|_| byte_unit::Byte::from_bytes(0))
                 .get_appropriate_unit(false);
                 writer.write_ok(
                     format!(
@@ -216,82 +726,382 @@ async fn query_writer(
             }
         }
         Err(e) => {
-            writer.write_err(
-                format!(
-                    "Query command error: cannot execute query with error: {:?}",
-                    e
-                )
-                .as_str(),
-            );
+            return Err(e);
         }
     }
     Ok(())
 }
 
-// TODO(zhihanz) mTLS support
-pub fn build_query_endpoint(status: &Status) -> Result<(reqwest::Client, String)> {
+pub fn build_query_endpoint(
+    status: &Status,
+    tls: &TlsOptions,
+) -> Result<(reqwest::Client, String)> {
     let query_configs = status.get_local_query_configs();
 
-    let (_, query) = query_configs.get(0).expect("cannot find query configs");
+    let (_, query) = query_configs
+        .get(0)
+        .ok_or_else(|| CliError::Unknown("cannot find query configs".to_string()))?;
+    build_endpoint_for(query, tls)
+}
+
+/// Build a `(client, url)` pair for every query node registered in the cluster,
+/// so a cluster-profile query can round-robin across them instead of talking to
+/// a single hard-coded local node.
+pub fn build_cluster_query_endpoints(
+    status: &Status,
+    tls: &TlsOptions,
+) -> Result<Vec<(reqwest::Client, String)>> {
+    let query_configs = status.get_local_query_configs();
+    if query_configs.is_empty() {
+        return Err(CliError::Unknown(
+            "cannot find any query configs in current cluster".to_string(),
+        ));
+    }
+    query_configs
+        .iter()
+        .map(|(_, query)| build_endpoint_for(query, tls))
+        .collect()
+}
+
+/// Build a single `(client, url)` pair for one query node, turning on HTTPS
+/// with mutual TLS once the node's config points at a server key/cert pair.
+/// The `--tls-*` overrides only replace the certificate paths; they do not
+/// turn on HTTPS for a node whose config has no key/cert pair.
+fn build_endpoint_for(
+    query: impl Borrow<LocalQueryConfig>,
+    tls: &TlsOptions,
+) -> Result<(reqwest::Client, String)> {
+    let query = query.borrow();
+    let address = format!(
+        "{}:{}",
+        query.config.query.http_handler_host, query.config.query.http_handler_port
+    )
+    .parse::<SocketAddr>()
+    .map_err(|e| CliError::Unknown(format!("cannot build query socket address: {:?}", e)))?;
+
+    let tls_enabled = !query.config.query.api_tls_server_key.is_empty()
+        && !query.config.query.api_tls_server_cert.is_empty();
+
+    if !tls_enabled {
+        let client = reqwest::Client::builder()
+            .build()
+            .map_err(|e| CliError::Unknown(format!("cannot build query client: {:?}", e)))?;
+        let url = format!("http://{}:{}/v1/statement", address.ip(), address.port());
+        return Ok((client, url));
+    }
+
+    let ca_path = tls
+        .ca
+        .clone()
+        .unwrap_or_else(|| query.config.query.api_tls_server_root_ca_cert.clone());
+    if ca_path.is_empty() {
+        return Err(CliError::Unknown(
+            "TLS is enabled but no CA certificate was found; pass --tls-ca or set api_tls_server_root_ca_cert".to_string(),
+        ));
+    }
+    let cert_path = tls
+        .cert
+        .clone()
+        .unwrap_or_else(|| query.config.query.api_tls_server_cert.clone());
+    let key_path = tls
+        .key
+        .clone()
+        .unwrap_or_else(|| query.config.query.api_tls_server_key.clone());
+
+    let ca_pem = std::fs::read(&ca_path)
+        .map_err(|e| CliError::Unknown(format!("cannot read TLS CA cert {}: {:?}", ca_path, e)))?;
+    let ca_cert = reqwest::Certificate::from_pem(&ca_pem).map_err(|e| {
+        CliError::Unknown(format!("cannot parse TLS CA cert {}: {:?}", ca_path, e))
+    })?;
+
+    let cert_pem = std::fs::read(&cert_path).map_err(|e| {
+        CliError::Unknown(format!("cannot read TLS client cert {}: {:?}", cert_path, e))
+    })?;
+    let key_pem = std::fs::read(&key_path)
+        .map_err(|e| CliError::Unknown(format!("cannot read TLS client key {}: {:?}", key_path, e)))?;
+    let mut identity_pem = cert_pem;
+    identity_pem.extend_from_slice(&key_pem);
+    let identity = reqwest::Identity::from_pem(&identity_pem)
+        .map_err(|e| CliError::Unknown(format!("cannot build TLS client identity: {:?}", e)))?;
+
     let client = reqwest::Client::builder()
+        .add_root_certificate(ca_cert)
+        .identity(identity)
+        .use_rustls_tls()
         .build()
-        .expect("Cannot build query client");
-
-    let url = {
-        if query.config.query.api_tls_server_key.is_empty()
-            || query.config.query.api_tls_server_cert.is_empty()
-        {
-            let address = format!(
-                "{}:{}",
-                query.config.query.http_handler_host, query.config.query.http_handler_port
-            )
-            .parse::<SocketAddr>()
-            .expect("cannot build query socket address");
-            format!("http://{}:{}/v1/statement", address.ip(), address.port())
+        .map_err(|e| CliError::Unknown(format!("cannot build TLS query client: {:?}", e)))?;
+
+    let url = format!("https://{}:{}/v1/statement", address.ip(), address.port());
+    Ok((client, url))
+}
+
+/// Health-check query nodes starting at the cluster's persisted round-robin
+/// index, returning the first live node and advancing the index past it so
+/// the next statement is sent to a different node. On a dead node the index
+/// keeps advancing, effectively failing over to the next candidate.
+async fn pick_cluster_endpoint(
+    endpoints: &[(reqwest::Client, String)],
+    status: &mut Status,
+) -> Option<(reqwest::Client, String)> {
+    let len = endpoints.len();
+    for offset in 0..len {
+        let idx = (status.query_node_round_robin_index + offset) % len;
+        let (cli, url) = &endpoints[idx];
+        if health_check(cli, url).await {
+            status.query_node_round_robin_index = (idx + 1) % len;
+            return Some((cli.clone(), url.clone()));
+        }
+    }
+    None
+}
+
+/// Probe the query node's dedicated health-check route rather than GETting
+/// the POST-only `/v1/statement` endpoint, so a live node correctly rejecting
+/// the wrong HTTP method (404/405) isn't mistaken for a dead one. Only a 2xx
+/// response counts as live.
+async fn health_check(cli: &reqwest::Client, url: &str) -> bool {
+    let health_url = url.replace("/v1/statement", "/v1/health");
+    match cli.get(&health_url).send().await {
+        Ok(resp) => resp.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+type HttpQueryResult = databend_query::servers::http::v1::statement::HttpQueryResult;
+
+/// Query result in a display-agnostic shape, so `query_writer` can hand it to
+/// whichever formatter the `--output` flag selected instead of always baking
+/// it into a `comfy_table::Table`. `rows` is the stringified form used by the
+/// `table`/`csv`/`tsv` formatters; `raw_rows` keeps the original typed values
+/// so `json`/`ndjson` can emit numbers and booleans unquoted.
+pub struct QueryResultData {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    pub raw_rows: Vec<Vec<serde_json::Value>>,
+}
+
+fn build_result_data(ans: &HttpQueryResult) -> QueryResultData {
+    let columns = ans
+        .columns
+        .as_ref()
+        .map(|column| {
+            column
+                .fields()
+                .iter()
+                .map(|field| field.name().clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    let raw_rows = ans.data.clone().unwrap_or_default();
+    let rows = raw_rows
+        .iter()
+        .map(|row| row.iter().map(|elem| elem.to_string()).collect())
+        .collect();
+    QueryResultData {
+        columns,
+        rows,
+        raw_rows,
+    }
+}
+
+/// Dispatch a query result to the formatter selected by `--output`, writing
+/// the structured body to stdout via `writer.writeln` so stats (printed
+/// separately via `writer.write_ok`) stay on stderr and don't pollute piped
+/// output.
+fn write_result(writer: &mut Writer, data: &QueryResultData, format: OutputFormat) {
+    match format {
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.load_preset("||--+-++|    ++++++");
+            table.set_header(data.columns.iter().map(|name| Cell::new(name).fg(Color::Green)));
+            for row in &data.rows {
+                table.add_row(row.iter().map(Cell::new));
+            }
+            writer.writeln(table.trim_fmt().as_str());
+        }
+        OutputFormat::Json => {
+            let objects: Vec<serde_json::Value> = data
+                .raw_rows
+                .iter()
+                .map(|row| {
+                    serde_json::Value::Object(
+                        data.columns.iter().cloned().zip(row.iter().cloned()).collect(),
+                    )
+                })
+                .collect();
+            writer.writeln(
+                serde_json::to_string_pretty(&objects)
+                    .unwrap_or_else(|_| "[]".to_string())
+                    .as_str(),
+            );
+        }
+        OutputFormat::Ndjson => {
+            for row in &data.raw_rows {
+                let object = serde_json::Value::Object(
+                    data.columns.iter().cloned().zip(row.iter().cloned()).collect(),
+                );
+                writer.writeln(serde_json::to_string(&object).unwrap_or_default().as_str());
+            }
+        }
+        OutputFormat::Csv => writer.writeln(render_delimited(data, ',').as_str()),
+        OutputFormat::Tsv => writer.writeln(render_delimited(data, '\t').as_str()),
+    }
+}
+
+/// Render a header row plus one row per result row, escaping any field that
+/// contains the delimiter, a quote, or a newline by quoting it and doubling
+/// embedded quotes (standard CSV/TSV quoting).
+fn render_delimited(data: &QueryResultData, delimiter: char) -> String {
+    let escape = |field: &str| -> String {
+        if field.contains(delimiter) || field.contains('"') || field.contains('\n') {
+            format!("\"{}\"", field.replace('"', "\"\""))
         } else {
-            todo!()
+            field.to_string()
         }
     };
-    Ok((client, url))
+    let mut lines = Vec::with_capacity(data.rows.len() + 1);
+    lines.push(
+        data.columns
+            .iter()
+            .map(|c| escape(c))
+            .collect::<Vec<_>>()
+            .join(&delimiter.to_string()),
+    );
+    for row in &data.rows {
+        lines.push(
+            row.iter()
+                .map(|f| escape(f))
+                .collect::<Vec<_>>()
+                .join(&delimiter.to_string()),
+        );
+    }
+    lines.join("\n")
 }
 
 async fn execute_query(
     cli: &reqwest::Client,
     url: &str,
     query: String,
-) -> Result<(Table, Option<ProgressValues>)> {
-    let ans = cli
-        .post(url)
-        .body(query.clone())
-        .send()
-        .await
-        .expect("cannot post to http handler")
-        .json::<databend_query::servers::http::v1::statement::HttpQueryResult>()
-        .await;
-    if let Err(e) = ans {
-        return Err(CliError::Unknown(format!(
+    database: Option<&str>,
+) -> std::result::Result<(QueryResultData, Option<ProgressValues>), QueryAttemptError> {
+    let mut req = cli.post(url).body(query.clone());
+    if let Some(db) = database {
+        req = req.header("x-databend-database", db);
+    }
+    let resp = req.send().await.map_err(|e| {
+        QueryAttemptError::Retryable(CliError::Unknown(format!(
+            "cannot post to http handler: {:?}",
+            e
+        )))
+    })?;
+    if resp.status().is_server_error() {
+        return Err(QueryAttemptError::Retryable(CliError::Unknown(format!(
+            "query node returned {}",
+            resp.status()
+        ))));
+    }
+    let ans = resp.json::<HttpQueryResult>().await.map_err(|e| {
+        QueryAttemptError::Fatal(CliError::Unknown(format!(
             "Cannot retrieve query result: {:?}",
             e
-        )));
-    } else {
-        let ans = ans.unwrap();
-        let mut table = Table::new();
-        table.load_preset("||--+-++|    ++++++");
-        if let Some(column) = ans.columns {
-            table.set_header(
-                column
-                    .fields()
-                    .iter()
-                    .map(|field| Cell::new(field.name().as_str()).fg(Color::Green)),
-            );
-        }
-        if let Some(rows) = ans.data {
-            for row in rows {
-                table.add_row(row.iter().map(|elem| Cell::new(elem.to_string())));
+        )))
+    })?;
+    let data = build_result_data(&ans);
+    Ok((data, ans.stats))
+}
+
+/// Render the query's progress as a live, in-place updating line by consuming
+/// the HTTP handler's response as a stream of newline-delimited progress
+/// frames instead of blocking on one large JSON body. Falls back to the
+/// buffered path when the server doesn't advertise streaming via the
+/// `x-databend-stream` response header.
+async fn execute_query_streaming(
+    cli: &reqwest::Client,
+    url: &str,
+    query: String,
+    database: Option<&str>,
+    _writer: &mut Writer,
+) -> std::result::Result<(QueryResultData, Option<ProgressValues>), QueryAttemptError> {
+    let mut req = cli.post(url).body(query.clone());
+    if let Some(db) = database {
+        req = req.header("x-databend-database", db);
+    }
+    let resp = req.send().await.map_err(|e| {
+        QueryAttemptError::Retryable(CliError::Unknown(format!(
+            "cannot post to http handler: {:?}",
+            e
+        )))
+    })?;
+    if resp.status().is_server_error() {
+        return Err(QueryAttemptError::Retryable(CliError::Unknown(format!(
+            "query node returned {}",
+            resp.status()
+        ))));
+    }
+
+    if resp.headers().get("x-databend-stream").is_none() {
+        let ans = resp.json::<HttpQueryResult>().await.map_err(|e| {
+            QueryAttemptError::Fatal(CliError::Unknown(format!(
+                "Cannot retrieve query result: {:?}",
+                e
+            )))
+        })?;
+        let data = build_result_data(&ans);
+        return Ok((data, ans.stats));
+    }
+
+    let mut stream = resp.bytes_stream();
+    let mut buf: Vec<u8> = Vec::new();
+    let mut last_stats: Option<ProgressValues> = None;
+    let mut final_result: Option<HttpQueryResult> = None;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| {
+            QueryAttemptError::Retryable(CliError::Unknown(format!("stream read error: {:?}", e)))
+        })?;
+        buf.extend_from_slice(&chunk);
+        while let Some(pos) = buf.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buf.drain(..=pos).collect();
+            let line = line.strip_suffix(b"\n").unwrap_or(&line);
+            if line.is_empty() {
+                continue;
+            }
+            let frame: HttpQueryResult = serde_json::from_slice(line).map_err(|e| {
+                QueryAttemptError::Fatal(CliError::Unknown(format!(
+                    "cannot parse progress frame: {:?}",
+                    e
+                )))
+            })?;
+            if let Some(stats) = &frame.stats {
+                print_progress_line(stats);
+                last_stats = Some(stats.clone());
+            }
+            if frame.columns.is_some() || frame.data.is_some() {
+                final_result = Some(frame);
             }
         }
-        Ok((table, ans.stats))
     }
+    // Clear the in-place progress line before the final table/output is printed.
+    eprint!("\r{}\r", " ".repeat(80));
+
+    let ans = final_result.ok_or_else(|| {
+        QueryAttemptError::Retryable(CliError::Unknown(
+            "query stream ended without a final result frame".to_string(),
+        ))
+    })?;
+    let data = build_result_data(&ans);
+    Ok((data, last_stats.or(ans.stats)))
+}
+
+fn print_progress_line(stats: &ProgressValues) {
+    use std::io::Write;
+    eprint!(
+        "\rread rows: {}, read bytes: {} ...",
+        stats.read_rows.to_formatted_string(&Locale::en),
+        byte_unit::Byte::from_bytes(stats.read_bytes as u128)
+            .get_appropriate_unit(false)
+    );
+    let _ = std::io::stderr().flush();
 }
 
 #[async_trait]
@@ -325,4 +1135,208 @@ impl Command for QueryCommand {
 
         Ok(())
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_statements_simple() {
+        assert_eq!(
+            split_statements("SELECT 1; SELECT 2;"),
+            vec!["SELECT 1".to_string(), "SELECT 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_no_trailing_semicolon() {
+        assert_eq!(
+            split_statements("SELECT 1; SELECT 2"),
+            vec!["SELECT 1".to_string(), "SELECT 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_drops_empty_and_whitespace() {
+        assert_eq!(
+            split_statements("  ; SELECT 1; ;  \n;  "),
+            vec!["SELECT 1".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_semicolon_in_single_quoted_string() {
+        assert_eq!(
+            split_statements("SELECT ';'; SELECT 2;"),
+            vec!["SELECT ';'".to_string(), "SELECT 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_escaped_single_quote() {
+        assert_eq!(
+            split_statements("SELECT 'a''b;c'; SELECT 2;"),
+            vec!["SELECT 'a''b;c'".to_string(), "SELECT 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_semicolon_in_double_quoted_identifier() {
+        assert_eq!(
+            split_statements(r#"SELECT "a;b" FROM t; SELECT 2;"#),
+            vec![r#"SELECT "a;b" FROM t"#.to_string(), "SELECT 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_semicolon_in_backtick_identifier() {
+        assert_eq!(
+            split_statements("SELECT `a;b` FROM t; SELECT 2;"),
+            vec!["SELECT `a;b` FROM t".to_string(), "SELECT 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_dollar_quoted_block() {
+        assert_eq!(
+            split_statements("SELECT $$a;b$$; SELECT 2;"),
+            vec!["SELECT $$a;b$$".to_string(), "SELECT 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_tagged_dollar_quoted_block() {
+        assert_eq!(
+            split_statements("SELECT $tag$a;b$tag$; SELECT 2;"),
+            vec!["SELECT $tag$a;b$tag$".to_string(), "SELECT 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_line_comment() {
+        assert_eq!(
+            split_statements("SELECT 1; -- a; b\nSELECT 2;"),
+            vec!["SELECT 1".to_string(), "-- a; b\nSELECT 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_block_comment() {
+        assert_eq!(
+            split_statements("SELECT 1; /* a; b */ SELECT 2;"),
+            vec!["SELECT 1".to_string(), "/* a; b */ SELECT 2".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_empty_input() {
+        assert_eq!(split_statements(""), Vec::<String>::new());
+        assert_eq!(split_statements("   \n  "), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_compute_backoff_ms_doubles_each_attempt() {
+        assert_eq!(compute_backoff_ms(200, 0), 200);
+        assert_eq!(compute_backoff_ms(200, 1), 400);
+        assert_eq!(compute_backoff_ms(200, 2), 800);
+        assert_eq!(compute_backoff_ms(200, 3), 1600);
+    }
+
+    #[test]
+    fn test_compute_backoff_ms_saturates_instead_of_panicking() {
+        assert_eq!(compute_backoff_ms(200, 64), u64::MAX);
+        assert_eq!(compute_backoff_ms(1, 63), 1u64 << 63);
+        assert_eq!(compute_backoff_ms(0, 64), 0);
+    }
+
+    #[test]
+    fn test_parse_use_database_basic() {
+        assert_eq!(
+            parse_use_database("USE mydb;"),
+            Some("mydb".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_use_database_case_insensitive_and_whitespace() {
+        assert_eq!(
+            parse_use_database("  use   mydb  ;  "),
+            Some("mydb".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_use_database_quoted() {
+        assert_eq!(parse_use_database("USE `mydb`;"), Some("mydb".to_string()));
+        assert_eq!(parse_use_database(r#"USE "mydb";"#), Some("mydb".to_string()));
+    }
+
+    #[test]
+    fn test_parse_use_database_no_trailing_semicolon() {
+        assert_eq!(parse_use_database("USE mydb"), Some("mydb".to_string()));
+    }
+
+    #[test]
+    fn test_parse_use_database_rejects_other_statements() {
+        assert_eq!(parse_use_database("SELECT 1;"), None);
+        assert_eq!(parse_use_database("USEMYDB;"), None);
+    }
+
+    #[test]
+    fn test_parse_use_database_rejects_empty_database_name() {
+        assert_eq!(parse_use_database("USE ;"), None);
+        assert_eq!(parse_use_database("USE"), None);
+    }
+
+    fn sample_data() -> QueryResultData {
+        QueryResultData {
+            columns: vec!["a".to_string(), "b".to_string()],
+            rows: vec![
+                vec!["1".to_string(), "x".to_string()],
+                vec!["2".to_string(), "y".to_string()],
+            ],
+            raw_rows: vec![],
+        }
+    }
+
+    #[test]
+    fn test_render_delimited_csv_basic() {
+        assert_eq!(render_delimited(&sample_data(), ','), "a,b\n1,x\n2,y");
+    }
+
+    #[test]
+    fn test_render_delimited_tsv_basic() {
+        assert_eq!(render_delimited(&sample_data(), '\t'), "a\tb\n1\tx\n2\ty");
+    }
+
+    #[test]
+    fn test_render_delimited_escapes_embedded_delimiter() {
+        let data = QueryResultData {
+            columns: vec!["a".to_string()],
+            rows: vec![vec!["x,y".to_string()]],
+            raw_rows: vec![],
+        };
+        assert_eq!(render_delimited(&data, ','), "a\n\"x,y\"");
+    }
+
+    #[test]
+    fn test_render_delimited_escapes_and_doubles_embedded_quote() {
+        let data = QueryResultData {
+            columns: vec!["a".to_string()],
+            rows: vec![vec![r#"he said "hi""#.to_string()]],
+            raw_rows: vec![],
+        };
+        assert_eq!(render_delimited(&data, ','), "a\n\"he said \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_render_delimited_escapes_embedded_newline() {
+        let data = QueryResultData {
+            columns: vec!["a".to_string()],
+            rows: vec![vec!["line1\nline2".to_string()]],
+            raw_rows: vec![],
+        };
+        assert_eq!(render_delimited(&data, ','), "a\n\"line1\nline2\"");
+    }
 }
\ No newline at end of file
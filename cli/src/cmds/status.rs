@@ -0,0 +1,102 @@
+// Copyright 2020 Datafuse Labs.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::cmds::Config;
+use crate::error::CliError;
+use crate::error::Result;
+
+/// A single local query node's on-disk config, as recorded when
+/// `bendctl cluster create` provisions it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct LocalQueryConfig {
+    pub config: databend_query::Config,
+}
+
+/// Persisted `bendctl` state for the current profile: the query nodes
+/// discovered on disk, plus session/runtime bookkeeping that needs to
+/// survive across separate `bendctl` invocations.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Status {
+    #[serde(skip)]
+    pub local_config_dir: String,
+    #[serde(default)]
+    pub local_query_configs: HashMap<String, LocalQueryConfig>,
+    /// Rotation cursor for cluster-profile query routing, persisted so
+    /// consecutive invocations keep spreading load across nodes instead of
+    /// always starting back at index 0.
+    #[serde(default)]
+    pub query_node_round_robin_index: usize,
+    /// Database set by the most recent `USE <database>;` statement,
+    /// persisted so the session context survives across invocations.
+    #[serde(default)]
+    pub current_database: Option<String>,
+}
+
+impl Status {
+    fn status_path(local_config_dir: &str) -> std::path::PathBuf {
+        Path::new(local_config_dir).join(".status.yaml")
+    }
+
+    pub fn read(conf: Config) -> Result<Self> {
+        let local_config_dir = conf.local_config_dir.clone();
+        let status_path = Self::status_path(&local_config_dir);
+        let mut status = if status_path.exists() {
+            let content = fs::read_to_string(&status_path)
+                .map_err(|e| CliError::Unknown(format!("cannot read status file: {:?}", e)))?;
+            serde_yaml::from_str(&content)
+                .map_err(|e| CliError::Unknown(format!("cannot parse status file: {:?}", e)))?
+        } else {
+            Status {
+                local_config_dir: local_config_dir.clone(),
+                local_query_configs: HashMap::new(),
+                query_node_round_robin_index: 0,
+                current_database: None,
+            }
+        };
+        status.local_config_dir = local_config_dir;
+        Ok(status)
+    }
+
+    pub fn write(&self) -> Result<()> {
+        let status_path = Self::status_path(&self.local_config_dir);
+        let content = serde_yaml::to_string(self)
+            .map_err(|e| CliError::Unknown(format!("cannot serialize status: {:?}", e)))?;
+        fs::write(&status_path, content)
+            .map_err(|e| CliError::Unknown(format!("cannot write status file: {:?}", e)))?;
+        Ok(())
+    }
+
+    pub fn has_local_configs(&self) -> bool {
+        !self.local_query_configs.is_empty()
+    }
+
+    /// Query node configs sorted by node name, so positions (and the
+    /// persisted round-robin index) refer to the same node across invocations.
+    pub fn get_local_query_configs(&self) -> Vec<(String, LocalQueryConfig)> {
+        let mut configs: Vec<(String, LocalQueryConfig)> = self
+            .local_query_configs
+            .iter()
+            .map(|(name, config)| (name.clone(), config.clone()))
+            .collect();
+        configs.sort_by(|a, b| a.0.cmp(&b.0));
+        configs
+    }
+}